@@ -9,6 +9,10 @@ pub struct InstallationEntry {
     /// by the user, and are used for distro-specific or package-manager-specific operations. For example, on Arch linux, a user
     /// may run `darling install joshuto --source=aur` to install a package such as joshuto from the AUR.
     pub properties: std::collections::HashMap<String, String>,
+
+    /// The specific version of the package to install, e.g. via `darling install foo --version=1.2.3`. If `None`,
+    /// the backend should install whatever it considers the latest available version.
+    pub version: Option<String>,
 }
 
 /// Global immutable data about the current darling session. This is currently almost entirely unused, but various
@@ -16,20 +20,224 @@ pub struct InstallationEntry {
 pub struct Context {
     /// The configuration cative when running darling.
     pub config: DarlingConfig,
+
+    /// Whether darling is running in dry-run / plan mode, e.g. via `darling install --dry-run`. When this is `true`,
+    /// `darling-core` calls [PackageManager::plan] and prints the resulting commands instead of calling
+    /// [PackageManager::install] or [PackageManager::uninstall].
+    pub dry_run: bool,
 }
 
 /// The user-defined configuration options.
 pub struct DarlingConfig {
     /// The location of the darling source on the users machine; `~/.local/share/darling/source` by default.
     pub source_location: String,
+
+    /// The location backends should use to fetch and build source packages, as used by [PackageManager::fetch_source]
+    /// and [PackageManager::build_source]; `~/.local/share/darling/build` by default.
+    pub build_cache_location: String,
 }
 
 impl std::default::Default for DarlingConfig {
     fn default() -> Self {
+        let home = std::env::var("HOME").unwrap();
         Self {
-            source_location: std::env::var("HOME").unwrap() + "/.local/share/darling/source",
+            source_location: home.clone() + "/.local/share/darling/source",
+            build_cache_location: home + "/.local/share/darling/build",
+        }
+    }
+}
+
+/// An optional behavior that a [PackageManager] backend may or may not support. `darling-core` uses
+/// [PackageManager::features] to check whether a backend supports a given capability before dispatching to the
+/// corresponding optional method, so it can emit a clean error (e.g. "backend `apt` does not support purge") instead
+/// of calling into a method the backend never implemented.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// The backend can delete a package's configuration files in addition to the package itself, via `purge`.
+    Purgeable,
+
+    /// The backend can install or report a specific version of a package, rather than only ever the latest.
+    Versionable,
+
+    /// The backend can upgrade already-installed packages to a newer version.
+    Upgradeable,
+
+    /// The backend can fetch and build a package from source rather than only installing a pre-built artifact.
+    InstallFromSource,
+
+    /// The backend can search its repositories for packages matching a query.
+    Searchable,
+
+    /// The backend can report which version of a package is currently installed.
+    HoldsVersion,
+
+    /// The backend can report a package's dependencies and reverse dependencies.
+    QueryDependencies,
+}
+
+/// Ranks a single position of a non-digit run the way dpkg's `verrevcmp` does: `~` sorts lower than everything else,
+/// a concluded run (the end of a string, or a digit beginning the next run) sorts just above `~` (so e.g. `"1.0"`
+/// is older than `"1.0a"`, and `"1"` is older than `"1a"`), ASCII letters sort by their own value, and every other
+/// byte sorts above all letters.
+fn non_digit_rank(c: Option<u8>) -> i32 {
+    match c {
+        None => 0,
+        Some(b'~') => -1,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => c as i32,
+        Some(c) => c as i32 + 256,
+    }
+}
+
+/// Compares two upstream version strings using the dpkg/pacman "alternating runs" algorithm: the strings are walked
+/// as alternating runs of non-digit and digit characters, non-digit runs are compared byte-by-byte via
+/// [non_digit_rank], and digit runs are compared numerically after stripping leading zeros. This is the free
+/// function backing [PackageManager::compare_versions]'s default implementation.
+fn compare_upstream_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let a = a.as_bytes();
+    let b = b.as_bytes();
+
+    let (mut i, mut j) = (0usize, 0usize);
+    while i < a.len() || j < b.len() {
+        while (i < a.len() && !a[i].is_ascii_digit()) || (j < b.len() && !b[j].is_ascii_digit()) {
+            let (ra, rb) = (
+                non_digit_rank(a.get(i).copied()),
+                non_digit_rank(b.get(j).copied()),
+            );
+            if ra != rb {
+                return ra.cmp(&rb);
+            }
+            if i < a.len() {
+                i += 1;
+            }
+            if j < b.len() {
+                j += 1;
+            }
+        }
+
+        while i < a.len() && a[i] == b'0' {
+            i += 1;
+        }
+        while j < b.len() && b[j] == b'0' {
+            j += 1;
+        }
+
+        let mut first_diff = 0i32;
+        while i < a.len() && a[i].is_ascii_digit() && j < b.len() && b[j].is_ascii_digit() {
+            if first_diff == 0 {
+                first_diff = a[i] as i32 - b[j] as i32;
+            }
+            i += 1;
+            j += 1;
+        }
+
+        if i < a.len() && a[i].is_ascii_digit() {
+            return std::cmp::Ordering::Greater;
+        }
+        if j < b.len() && b[j].is_ascii_digit() {
+            return std::cmp::Ordering::Less;
+        }
+        if first_diff != 0 {
+            return first_diff.cmp(&0);
         }
     }
+
+    std::cmp::Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn tilde_sorts_as_pre_release() {
+        assert_eq!(compare_upstream_versions("1.0~rc1", "1.0"), Ordering::Less);
+        assert_eq!(
+            compare_upstream_versions("1.0", "1.0~rc1"),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn end_of_string_sorts_below_trailing_letter() {
+        assert_eq!(compare_upstream_versions("1.0", "1.0a"), Ordering::Less);
+        assert_eq!(compare_upstream_versions("1.0a", "1.0"), Ordering::Greater);
+    }
+
+    #[test]
+    fn concluded_run_at_a_digit_boundary_sorts_below_trailing_letter() {
+        assert_eq!(compare_upstream_versions("1", "a"), Ordering::Less);
+        assert_eq!(compare_upstream_versions("a", "1"), Ordering::Greater);
+    }
+
+    #[test]
+    fn leading_zeros_are_stripped_before_numeric_comparison() {
+        assert_eq!(compare_upstream_versions("007", "7"), Ordering::Equal);
+    }
+
+    #[test]
+    fn digit_runs_compare_by_magnitude_not_length() {
+        assert_eq!(compare_upstream_versions("10", "9"), Ordering::Greater);
+        assert_eq!(compare_upstream_versions("1.2.3", "1.2.10"), Ordering::Less);
+    }
+
+    #[test]
+    fn equal_versions_are_equal() {
+        assert_eq!(compare_upstream_versions("1.2.3", "1.2.3"), Ordering::Equal);
+        assert_eq!(compare_upstream_versions("007", "007"), Ordering::Equal);
+    }
+}
+
+/// An operation that can be simulated via [PackageManager::plan].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// Simulate a call to [PackageManager::install].
+    Install,
+
+    /// Simulate a call to [PackageManager::uninstall].
+    Uninstall,
+}
+
+/// The reason a package is currently installed, mirroring the distinction apt and pacman both track between packages
+/// a user asked for directly and packages that were only pulled in to satisfy a dependency.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InstallReason {
+    /// The user explicitly requested this package, e.g. via `darling install`.
+    Explicit,
+
+    /// This package was installed only because another package depends on it.
+    Dependency,
+}
+
+/// Metadata about a package, as returned by [PackageManager::search] and [PackageManager::get_info]. This lets
+/// users discover and inspect packages before installing them, the way `amethyst`'s AUR RPC lookups or rust-apt's
+/// package records do.
+#[derive(Clone, Debug)]
+pub struct PackageInfo {
+    /// The name of the package. This is a unique identifier, not a human readable string.
+    pub name: String,
+
+    /// The version of the package that this info describes.
+    pub version: String,
+
+    /// A human-readable description of the package.
+    pub description: String,
+
+    /// The package's homepage or upstream project URL, if known.
+    pub homepage: Option<String>,
+
+    /// The names of this package's dependencies, as would be returned by [PackageManager::get_dependencies].
+    pub dependencies: Vec<String>,
+
+    /// The size, in bytes, of the package's download artifact, if known.
+    pub download_size: Option<u64>,
+
+    /// The size, in bytes, the package takes up once installed, if known.
+    pub installed_size: Option<u64>,
+
+    /// The name of the repository or source this package comes from, e.g. `"core"` or `"aur"`.
+    pub repository: String,
 }
 
 /// A package manager which gets a darling implementation. This provides the core functionality on how to install,
@@ -82,6 +290,52 @@ pub trait PackageManager: Send + Sync {
         Ok(())
     }
 
+    /// Fetches the source of a package into a build directory under `context.config.build_cache_location`, e.g. by
+    /// cloning a PKGBUILD repository or downloading a source tarball. This requires [Capability::InstallFromSource];
+    /// backends which don't declare that capability can rely on the default implementation, which simply errors.
+    ///
+    /// Together with [build_source], this is the fetch→build→install pipeline used by backends such as AUR helpers,
+    /// which install by compiling source rather than by invoking a pre-built package. `install` may call both of
+    /// these itself when the user requests a source install, e.g. via `darling install joshuto --source=aur`.
+    ///
+    /// # Parameters
+    /// - `context` - The darling context, which provides global immutable information about the program.
+    /// - `package` - The package whose source should be fetched.
+    ///
+    /// # Returns
+    /// The directory the source was fetched into, ready to be passed to [build_source].
+    fn fetch_source(
+        &self,
+        _context: &Context,
+        _package: &InstallationEntry,
+    ) -> anyhow::Result<std::path::PathBuf> {
+        anyhow::bail!(
+            "backend `{}` does not support installing from source",
+            self.name()
+        )
+    }
+
+    /// Builds a package from a source directory previously returned by [fetch_source], e.g. by running `makepkg`.
+    /// This requires [Capability::InstallFromSource]; backends which don't declare that capability can rely on the
+    /// default implementation, which simply errors.
+    ///
+    /// # Parameters
+    /// - `context` - The darling context, which provides global immutable information about the program.
+    /// - `source_dir` - The directory returned by [fetch_source].
+    ///
+    /// # Returns
+    /// The path to the built package artifact, ready to be installed.
+    fn build_source(
+        &self,
+        _context: &Context,
+        _source_dir: &std::path::Path,
+    ) -> anyhow::Result<std::path::PathBuf> {
+        anyhow::bail!(
+            "backend `{}` does not support installing from source",
+            self.name()
+        )
+    }
+
     /// Uninstalls a package from the system. This does ***not*** affect the cache file, it simply removes the package
     /// from the system itself, and `darling-core` will handle removing the package from the cache file.
     ///
@@ -93,6 +347,50 @@ pub trait PackageManager: Send + Sync {
     /// An error if the package could not be removed.
     fn uninstall(&self, context: &Context, package: &InstallationEntry) -> anyhow::Result<()>;
 
+    /// Returns the exact shell commands that [install] or [uninstall] would run for `package`, without actually
+    /// running them. `darling-core` calls this instead of [install]/[uninstall] when `context.dry_run` is `true`,
+    /// and prints the returned commands to the user.
+    ///
+    /// Backends building a [std::process::Command] can share one helper between this method and [install]/
+    /// [uninstall] to both print and execute the same command, so there's no risk of the plan drifting from what
+    /// actually runs.
+    ///
+    /// This method is optional, and has a default implementation that simply errors.
+    ///
+    /// # Parameters
+    /// - `context` - The darling context, which provides global immutable information about the program.
+    /// - `package` - The package the operation would apply to.
+    /// - `op` - Which operation to simulate.
+    ///
+    /// # Returns
+    /// The shell commands that would be run, in order.
+    fn plan(
+        &self,
+        _context: &Context,
+        _package: &InstallationEntry,
+        _op: Operation,
+    ) -> anyhow::Result<Vec<String>> {
+        anyhow::bail!(
+            "backend `{}` does not support dry-run planning",
+            self.name()
+        )
+    }
+
+    /// Uninstalls a package from the system **and** removes its configuration files, the way `apt purge` or
+    /// `dpkg --purge` do. This is distinct from [uninstall], which should leave configuration in place. This
+    /// requires [Capability::Purgeable]; backends which don't declare that capability can rely on the default
+    /// implementation, which simply errors.
+    ///
+    /// # Parameters
+    /// - `context` - The darling context, which provides global immutable information about the program.
+    /// - `package` - The name of the package to purge.
+    ///
+    /// # Returns
+    /// An error if the package could not be purged.
+    fn purge(&self, _context: &Context, _package: &InstallationEntry) -> anyhow::Result<()> {
+        anyhow::bail!("backend `{}` does not support purge", self.name())
+    }
+
     /// Returns all *explicitly* installed packages on the system; That is, packages which are not dependencies of
     /// other packages. This **should not** read from a darling file; Instead, darling uses this method to update
     /// the file when running `darling require-all`
@@ -103,4 +401,151 @@ pub trait PackageManager: Send + Sync {
     /// # Returns
     /// The name and version of each installed package. as a `Vec<(name: String, version: String)>`.
     fn get_all_explicit(&self, context: &Context) -> anyhow::Result<Vec<(String, String)>>;
+
+    /// Returns every installed package on the system, explicit or not, tagged with why it's installed. Unlike
+    /// [get_all_explicit], this also reports packages pulled in as dependencies, which lets `darling-core` tell
+    /// apart user-requested packages from dependency packages when deciding what `require-all` should track.
+    ///
+    /// This method is optional, and has a default implementation that simply errors.
+    ///
+    /// # Parameters
+    /// - `context` - The darling context, which provides global immutable information about the program.
+    ///
+    /// # Returns
+    /// The name, version, and [InstallReason] of each installed package.
+    fn get_all_installed(
+        &self,
+        _context: &Context,
+    ) -> anyhow::Result<Vec<(String, String, InstallReason)>> {
+        anyhow::bail!(
+            "backend `{}` does not support querying all installed packages",
+            self.name()
+        )
+    }
+
+    /// Returns the names of the packages that `package` directly depends on. This requires
+    /// [Capability::QueryDependencies]; backends which don't declare that capability can rely on the default
+    /// implementation, which simply errors.
+    ///
+    /// `darling-core` uses this, together with [get_reverse_dependencies], to implement dependency-aware removal
+    /// and orphan cleanup: walking the reverse-dependency graph to find packages no longer required by anything
+    /// explicitly installed, and warning before removing a package other packages still depend on.
+    ///
+    /// # Parameters
+    /// - `context` - The darling context, which provides global immutable information about the program.
+    /// - `package` - The name of the package to query.
+    ///
+    /// # Returns
+    /// The names of `package`'s direct dependencies.
+    fn get_dependencies(&self, _context: &Context, _package: &str) -> anyhow::Result<Vec<String>> {
+        anyhow::bail!(
+            "backend `{}` does not support querying package dependencies",
+            self.name()
+        )
+    }
+
+    /// Returns the names of the packages that directly depend on `package`. This requires
+    /// [Capability::QueryDependencies]; backends which don't declare that capability can rely on the default
+    /// implementation, which simply errors.
+    ///
+    /// # Parameters
+    /// - `context` - The darling context, which provides global immutable information about the program.
+    /// - `package` - The name of the package to query.
+    ///
+    /// # Returns
+    /// The names of the packages that directly depend on `package`.
+    fn get_reverse_dependencies(
+        &self,
+        _context: &Context,
+        _package: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        anyhow::bail!(
+            "backend `{}` does not support querying reverse package dependencies",
+            self.name()
+        )
+    }
+
+    /// Searches this backend's repositories for packages matching `query`. This requires [Capability::Searchable];
+    /// backends which don't declare that capability can rely on the default implementation, which simply errors.
+    ///
+    /// This powers the `darling search` subcommand uniformly across backends.
+    ///
+    /// # Parameters
+    /// - `context` - The darling context, which provides global immutable information about the program.
+    /// - `query` - The search term to match against package names and descriptions.
+    ///
+    /// # Returns
+    /// The packages matching `query`.
+    fn search(&self, _context: &Context, _query: &str) -> anyhow::Result<Vec<PackageInfo>> {
+        anyhow::bail!(
+            "backend `{}` does not support searching for packages",
+            self.name()
+        )
+    }
+
+    /// Returns metadata about a single package, whether or not it's installed. This requires
+    /// [Capability::Searchable]; backends which don't declare that capability can rely on the default
+    /// implementation, which simply errors.
+    ///
+    /// This powers the `darling info` subcommand uniformly across backends, and its `dependencies` field dovetails
+    /// with [get_dependencies] for richer output.
+    ///
+    /// # Parameters
+    /// - `context` - The darling context, which provides global immutable information about the program.
+    /// - `package` - The name of the package to look up.
+    ///
+    /// # Returns
+    /// Metadata about `package`.
+    fn get_info(&self, _context: &Context, _package: &str) -> anyhow::Result<PackageInfo> {
+        anyhow::bail!(
+            "backend `{}` does not support package info lookup",
+            self.name()
+        )
+    }
+
+    /// Returns the set of optional [Capability] values this backend supports. `darling-core` consults this before
+    /// calling the capability-gated optional methods on this trait (such as purge, get_upgradable, or search), so that
+    /// an unsupported operation fails with a clean "backend does not support X" error instead of an
+    /// unimplemented-method panic. [plan], [get_all_installed] and [post_install] have no corresponding [Capability].
+    ///
+    /// The default implementation declares no optional capabilities. Backends should override this to return
+    /// whichever [Capability] variants they actually implement.
+    ///
+    /// # Returns
+    /// The set of capabilities this backend supports.
+    fn features(&self) -> std::collections::HashSet<Capability> {
+        std::collections::HashSet::new()
+    }
+
+    /// Compares two upstream version strings, returning whether `a` is older than, equal to, or newer than `b`.
+    /// `darling-core` uses this to decide whether an installed package is out of date.
+    ///
+    /// The default implementation follows the dpkg/pacman upstream-version algorithm, which is suitable for most
+    /// backends. Override this if the backend's package manager has its own, incompatible versioning scheme.
+    ///
+    /// # Parameters
+    /// - `a` - The first version string to compare.
+    /// - `b` - The second version string to compare.
+    ///
+    /// # Returns
+    /// [std::cmp::Ordering::Less] if `a` is older than `b`, [std::cmp::Ordering::Equal] if they are the same
+    /// version, or [std::cmp::Ordering::Greater] if `a` is newer than `b`.
+    fn compare_versions(&self, a: &str, b: &str) -> std::cmp::Ordering {
+        compare_upstream_versions(a, b)
+    }
+
+    /// Returns every installed package that has a newer version available. This requires [Capability::Upgradeable];
+    /// backends which don't declare that capability can rely on the default implementation, which simply errors.
+    ///
+    /// # Parameters
+    /// - `context` - The darling context, which provides global immutable information about the program.
+    ///
+    /// # Returns
+    /// A `Vec` of `(name, installed_version, candidate_version)` for each package with an upgrade available.
+    fn get_upgradable(&self, _context: &Context) -> anyhow::Result<Vec<(String, String, String)>> {
+        anyhow::bail!(
+            "backend `{}` does not support listing upgradable packages",
+            self.name()
+        )
+    }
 }